@@ -24,6 +24,36 @@ use std::path::{Path, PathBuf};
 /// - `Ok(None)` if no files are available in the inbox.
 /// - `Err(io::Error)` if an I/O error occurs during the operation.
 pub fn next_file(inbox_dir: &Path, work_dir: &Path) -> io::Result<Option<PathBuf>> {
+    next_file_matching(inbox_dir, work_dir, |_| true)
+}
+
+/// Attempts to claim the next available file from the inbox directory that
+/// satisfies the provided predicate, by moving it to the work directory.
+///
+/// Files for which the predicate returns `false` are left in the inbox for
+/// some other process to claim. Otherwise, this behaves like `next_file`.
+///
+/// # Parameters
+///
+/// - `inbox_dir`: Path to the directory containing incoming files.
+/// - `work_dir`: Path to the directory where the file will be moved for processing.
+/// - `is_match`: Predicate called with the inbox path of each candidate file.
+///
+/// # Returns
+///
+/// - `Ok(Some(PathBuf))` if a matching file is successfully claimed and moved.
+/// - `Ok(None)` if no matching files are available in the inbox.
+/// - `Err(io::Error)` if an I/O error occurs during the operation.
+pub fn next_file_matching<F>(
+    inbox_dir: &Path,
+    work_dir: &Path,
+    is_match: F,
+) -> io::Result<Option<PathBuf>>
+where
+    F: Fn(&Path) -> bool,
+{
+    // keep track of the files we passed over, to log about them once
+    let mut skipped = 0;
     // create an iterator over the entries in the inbox_dir
     let entries = fs::read_dir(inbox_dir)?;
     // for each entry in the inbox_dir
@@ -34,6 +64,11 @@ pub fn next_file(inbox_dir: &Path, work_dir: &Path) -> io::Result<Option<PathBuf
         let src_path = entry.path();
         // if this entry is a file (and not a directory or a link or something)
         if src_path.is_file() {
+            // if this file isn't one we're interested in, skip it
+            if !is_match(&src_path) {
+                skipped += 1;
+                continue;
+            }
             // determine the work_dir path for this file
             let file_name = src_path.file_name().unwrap();
             let dest_path = work_dir.join(file_name);
@@ -42,6 +77,9 @@ pub fn next_file(inbox_dir: &Path, work_dir: &Path) -> io::Result<Option<PathBuf
                 // if we were able to move it to the work_dir
                 Ok(_) => {
                     // return the work_dir path of the file
+                    if skipped > 0 {
+                        trace!("next_file({inbox_dir:?}): Skipped {skipped} unmatched file(s)");
+                    }
                     info!("Moved {src_path:?} -> {dest_path:?}");
                     return Ok(Some(dest_path));
                 }
@@ -62,7 +100,11 @@ pub fn next_file(inbox_dir: &Path, work_dir: &Path) -> io::Result<Option<PathBuf
     }
     // NO FILE FOR YOU!
     // https://www.youtube.com/watch?v=zOpfsGrNvnk
-    trace!("next_file({inbox_dir:?}): No files present");
+    if skipped > 0 {
+        trace!("next_file({inbox_dir:?}): No matching files present ({skipped} unmatched)");
+    } else {
+        trace!("next_file({inbox_dir:?}): No files present");
+    }
     Ok(None)
 }
 
@@ -72,10 +114,44 @@ pub fn next_file(inbox_dir: &Path, work_dir: &Path) -> io::Result<Option<PathBuf
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_always_succeed() {
         assert!(true);
     }
+
+    #[test]
+    fn test_next_file_empty_inbox() {
+        let inbox_dir = tempfile::tempdir().unwrap();
+        let work_dir = tempfile::tempdir().unwrap();
+        let result = next_file(inbox_dir.path(), work_dir.path()).unwrap();
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_next_file_claims_file() {
+        let inbox_dir = tempfile::tempdir().unwrap();
+        let work_dir = tempfile::tempdir().unwrap();
+        fs::write(inbox_dir.path().join("check_work.aa"), "").unwrap();
+        let result = next_file(inbox_dir.path(), work_dir.path()).unwrap();
+        assert_eq!(result, Some(work_dir.path().join("check_work.aa")));
+        assert!(!inbox_dir.path().join("check_work.aa").exists());
+        assert!(work_dir.path().join("check_work.aa").exists());
+    }
+
+    #[test]
+    fn test_next_file_matching_skips_unmatched() {
+        let inbox_dir = tempfile::tempdir().unwrap();
+        let work_dir = tempfile::tempdir().unwrap();
+        fs::write(inbox_dir.path().join("check_work.aa"), "").unwrap();
+        fs::write(inbox_dir.path().join("check_work.ab"), "").unwrap();
+        let is_match = |path: &Path| path.ends_with("check_work.ab");
+        let result = next_file_matching(inbox_dir.path(), work_dir.path(), is_match).unwrap();
+        assert_eq!(result, Some(work_dir.path().join("check_work.ab")));
+        assert!(inbox_dir.path().join("check_work.aa").exists());
+        let result = next_file_matching(inbox_dir.path(), work_dir.path(), is_match).unwrap();
+        assert_eq!(result, None);
+    }
 }
@@ -51,16 +51,20 @@
 /// Files that do not pass verification can send the work unit to a
 /// quarantine directory along with a note of the file(s) that caused
 /// the work unit to be quarantined for further examination.
+use chrono::{DateTime, NaiveDate, Utc};
 use log::{error, info, trace, warn};
+use serde::Serialize;
 use std::env;
+use std::ffi::OsStr;
 use std::fs;
-use std::io::Write;
+use std::io::{Read, Write};
+use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use wipac_datamove::adhoc::utils::next_file;
+use wipac_datamove::adhoc::utils::next_file_matching;
 
 pub type Error = Box<dyn core::error::Error>;
 pub type Result<T> = core::result::Result<T, Error>;
@@ -69,11 +73,267 @@ pub type Result<T> = core::result::Result<T, Error>;
 pub struct Context {
     pub debug_delay_seconds: u64,
     pub inbox_dir: String,
+    pub note_format: NoteFormat,
     pub outbox_dir: String,
     pub quarantine_dir: String,
     pub run_once_and_die: bool,
+    pub sha512sum_args: Vec<String>,
+    pub sha512sum_bin: String,
+    pub sha512sum_parallelism: usize,
+    pub throughput_report_seconds: u64,
     pub work_dir: String,
     pub work_sleep_seconds: u64,
+    pub work_unit_filter: WorkUnitFilter,
+}
+
+/// the source of the date used to filter which work units are claimed
+#[derive(Debug, PartialEq)]
+pub enum DateFilterSource {
+    /// a date embedded in the filename; YYYY-MM-DD or YYYYMMDD (default)
+    Filename,
+    /// the modification time of the file
+    Mtime,
+}
+
+impl std::str::FromStr for DateFilterSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        match s {
+            "filename" => Ok(DateFilterSource::Filename),
+            "mtime" => Ok(DateFilterSource::Mtime),
+            _ => Err(format!(
+                "Unknown date filter source '{s}'; expected 'filename' or 'mtime'"
+            )),
+        }
+    }
+}
+
+/// a filter that restricts which work units are claimed from the inbox
+///
+/// A work unit matches if its date is on or after `since` and before
+/// `until`. Without `since` or `until`, every work unit matches. With a
+/// filter in place, work units without a date do not match.
+#[derive(Debug)]
+pub struct WorkUnitFilter {
+    pub since: Option<NaiveDate>,
+    pub source: DateFilterSource,
+    pub until: Option<NaiveDate>,
+}
+
+impl WorkUnitFilter {
+    pub fn is_active(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.since.is_none_or(|since| date >= since) && self.until.is_none_or(|until| date < until)
+    }
+
+    pub fn is_match(&self, path: &Path) -> bool {
+        // no filter, no problem
+        if !self.is_active() {
+            return true;
+        }
+        // determine the date of the work unit
+        let date = match self.source {
+            DateFilterSource::Filename => filename_date(path),
+            DateFilterSource::Mtime => mtime_date(path),
+        };
+        // without a date, we can't say it belongs to this campaign
+        date.is_some_and(|date| self.contains(date))
+    }
+}
+
+/// Find the first date embedded in the filename of the provided path,
+/// formatted as either YYYY-MM-DD or YYYYMMDD.
+fn filename_date(path: &Path) -> Option<NaiveDate> {
+    let name = path.file_name()?.as_encoded_bytes();
+    let digits = |bytes: &[u8]| -> Option<u32> {
+        if !bytes.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        std::str::from_utf8(bytes).ok()?.parse().ok()
+    };
+    let ymd = |year: &[u8], month: &[u8], day: &[u8]| -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(digits(year)? as i32, digits(month)?, digits(day)?)
+    };
+    // look for YYYY-MM-DD first, as it is the least ambiguous
+    let dashed = name.windows(10).find_map(|w| {
+        if w[4] != b'-' || w[7] != b'-' {
+            return None;
+        }
+        ymd(&w[0..4], &w[5..7], &w[8..10])
+    });
+    // otherwise, fall back to YYYYMMDD
+    dashed.or_else(|| {
+        name.windows(8)
+            .find_map(|w| ymd(&w[0..4], &w[4..6], &w[6..8]))
+    })
+}
+
+/// Determine the date (UTC) of the modification time of the provided path.
+fn mtime_date(path: &Path) -> Option<NaiveDate> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(DateTime::<Utc>::from(modified).date_naive())
+}
+
+/// the format used when writing a note file to the quarantine directory
+#[derive(Debug, PartialEq)]
+pub enum NoteFormat {
+    /// raw stdout and stderr of the sha512sum command (default)
+    Text,
+    /// a structured JSON document; see `Note`
+    Json,
+}
+
+impl std::str::FromStr for NoteFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(NoteFormat::Text),
+            "json" => Ok(NoteFormat::Json),
+            _ => Err(format!(
+                "Unknown note format '{s}'; expected 'text' or 'json'"
+            )),
+        }
+    }
+}
+
+/// a structured note describing why a work unit was quarantined
+#[derive(Debug, Serialize)]
+pub struct Note {
+    pub work_unit: String,
+    pub exit_code: i32,
+    pub failed_files: Vec<String>,
+    pub failures: Vec<Failure>,
+    pub stderr: String,
+}
+
+/// a file that failed verification, and why we think it failed
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Failure {
+    pub path: String,
+    pub classification: Classification,
+}
+
+/// the kind of corruption identified in a file that failed verification
+///
+/// The checkfile does not record the expected size of each file, so a
+/// truncated file that still contains data is a `ChecksumMismatch`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Classification {
+    /// the file does not exist
+    Missing,
+    /// the file exists, but can't be opened or read (e.g. EIO)
+    Unreadable,
+    /// the file has a length of 0 bytes
+    ZeroLength,
+    /// the file has a length, but every byte is 0x00
+    AllZeroBytes,
+    /// the file has non-zero content, but the checksum doesn't match
+    ChecksumMismatch,
+}
+
+impl std::fmt::Display for Classification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Classification::Missing => "missing",
+            Classification::Unreadable => "unreadable",
+            Classification::ZeroLength => "zero-length",
+            Classification::AllZeroBytes => "all-zero-bytes",
+            Classification::ChecksumMismatch => "checksum-mismatch",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// the number of files and bytes described by a single work unit
+#[derive(Debug, Default, PartialEq)]
+pub struct WorkUnitSize {
+    pub files: u64,
+    pub bytes: u64,
+}
+
+/// accounting of the work processed during a work cycle
+#[derive(Debug)]
+pub struct Throughput {
+    pub bytes: u64,
+    pub files: u64,
+    pub total_work_units: Option<u64>,
+    pub work_units: u64,
+}
+
+impl Throughput {
+    pub fn new(total_work_units: Option<u64>) -> Self {
+        Throughput {
+            bytes: 0,
+            files: 0,
+            total_work_units,
+            work_units: 0,
+        }
+    }
+
+    /// account for a work unit that has been processed
+    pub fn record(&mut self, size: &WorkUnitSize) {
+        self.bytes += size.bytes;
+        self.files += size.files;
+        self.work_units += 1;
+    }
+
+    pub fn files_per_sec(&self, elapsed: Duration) -> f64 {
+        per_sec(self.files as f64, elapsed)
+    }
+
+    pub fn mb_per_sec(&self, elapsed: Duration) -> f64 {
+        per_sec(self.bytes as f64 / 1_000_000.0, elapsed)
+    }
+
+    /// estimate the time remaining to process the rest of the work units
+    ///
+    /// Returns `None` if the total is unknown or nothing has been processed.
+    /// Other replicas consuming the same inbox will make this pessimistic.
+    pub fn eta(&self, elapsed: Duration) -> Option<Duration> {
+        let total_work_units = self.total_work_units?;
+        if self.work_units == 0 {
+            return None;
+        }
+        let remaining = total_work_units.saturating_sub(self.work_units);
+        let per_work_unit = elapsed.as_secs_f64() / self.work_units as f64;
+        Some(Duration::from_secs_f64(per_work_unit * remaining as f64))
+    }
+
+    /// build a human readable report of the throughput so far
+    pub fn report(&self, elapsed: Duration) -> String {
+        let mut report = format!(
+            "{} work unit(s), {} file(s), {} byte(s) in {:.1}s; {:.2} files/sec, {:.2} MB/sec",
+            self.work_units,
+            self.files,
+            self.bytes,
+            elapsed.as_secs_f64(),
+            self.files_per_sec(elapsed),
+            self.mb_per_sec(elapsed),
+        );
+        if let Some(eta) = self.eta(elapsed) {
+            let total_work_units = self.total_work_units.unwrap_or_default();
+            report.push_str(&format!(
+                " ({}/{total_work_units} work unit(s); ETA {}s)",
+                self.work_units,
+                eta.as_secs()
+            ));
+        }
+        report
+    }
+}
+
+fn per_sec(amount: f64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds == 0.0 {
+        return 0.0;
+    }
+    amount / seconds
 }
 
 pub fn load_context() -> Context {
@@ -82,11 +342,53 @@ pub fn load_context() -> Context {
     let debug_delay_seconds: u64 = debug_delay_seconds_str
         .parse()
         .expect("Unable to parse DEBUG_DELAY_SECONDS as an integer (u64)");
+    let date_filter_source_str = env::var("DATE_FILTER_SOURCE").unwrap_or("filename".to_string());
+    let date_filter_source: DateFilterSource = date_filter_source_str
+        .parse()
+        .expect("Unable to parse DATE_FILTER_SOURCE as 'filename' or 'mtime'");
+    let since: Option<NaiveDate> = env::var("SINCE")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .map(|since| {
+            since
+                .parse()
+                .expect("Unable to parse SINCE as a date (YYYY-MM-DD)")
+        });
+    let until: Option<NaiveDate> = env::var("UNTIL")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .map(|until| {
+            until
+                .parse()
+                .expect("Unable to parse UNTIL as a date (YYYY-MM-DD)")
+        });
+    let note_format_str = env::var("NOTE_FORMAT").unwrap_or("text".to_string());
+    let note_format: NoteFormat = note_format_str
+        .parse()
+        .expect("Unable to parse NOTE_FORMAT as 'text' or 'json'");
     let run_once_and_die_str =
         env::var("RUN_ONCE_AND_DIE").expect("RUN_ONCE_AND_DIE environment variable not set");
     let run_once_and_die: bool = run_once_and_die_str
         .parse()
         .expect("Unable to parse RUN_ONCE_AND_DIE as a bool");
+    let sha512sum_args: Vec<String> = env::var("SHA512SUM_ARGS")
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|arg| arg.to_string())
+        .collect();
+    let sha512sum_parallelism_str = env::var("SHA512SUM_PARALLELISM").unwrap_or("1".to_string());
+    let sha512sum_parallelism: usize = sha512sum_parallelism_str
+        .parse()
+        .expect("Unable to parse SHA512SUM_PARALLELISM as an integer (usize)");
+    assert!(
+        sha512sum_parallelism >= 1,
+        "SHA512SUM_PARALLELISM must be at least 1"
+    );
+    let throughput_report_seconds_str =
+        env::var("THROUGHPUT_REPORT_SECONDS").unwrap_or("60".to_string());
+    let throughput_report_seconds: u64 = throughput_report_seconds_str
+        .parse()
+        .expect("Unable to parse THROUGHPUT_REPORT_SECONDS as an integer (u64)");
     let work_sleep_seconds_str =
         env::var("WORK_SLEEP_SECONDS").expect("WORK_SLEEP_SECONDS environment variable not set");
     let work_sleep_seconds: u64 = work_sleep_seconds_str
@@ -96,12 +398,22 @@ pub fn load_context() -> Context {
     Context {
         debug_delay_seconds,
         inbox_dir: env::var("INBOX_DIR").expect("INBOX_DIR environment variable not set"),
+        note_format,
         outbox_dir: env::var("OUTBOX_DIR").expect("OUTBOX_DIR environment variable not set"),
         quarantine_dir: env::var("QUARANTINE_DIR")
             .expect("QUARANTINE_DIR environment variable not set"),
         run_once_and_die,
+        sha512sum_args,
+        sha512sum_bin: env::var("SHA512SUM_BIN").unwrap_or("sha512sum".to_string()),
+        sha512sum_parallelism,
+        throughput_report_seconds,
         work_dir: env::var("WORK_DIR").expect("WORK_DIR environment variable not set"),
         work_sleep_seconds,
+        work_unit_filter: WorkUnitFilter {
+            since,
+            source: date_filter_source,
+            until,
+        },
     }
 }
 
@@ -115,29 +427,387 @@ fn build_note_path(quarantine_dir: &Path, file: &Path) -> PathBuf {
     quarantine_dir.join(note_name)
 }
 
+/// Extract the paths of the files that failed verification from the
+/// stdout of `sha512sum --check`.
+///
+/// Lines look like `/path/to/my.data: FAILED` for a checksum mismatch,
+/// or `/path/to/my.data: FAILED open or read` for a missing file. The
+/// output is parsed as bytes, because paths need not be valid UTF-8.
+fn parse_failed_files(stdout: &[u8]) -> Vec<PathBuf> {
+    stdout
+        .split(|byte| *byte == b'\n')
+        .filter_map(|line| {
+            line.strip_suffix(b": FAILED")
+                .or_else(|| line.strip_suffix(b": FAILED open or read"))
+        })
+        .map(|path| PathBuf::from(OsStr::from_bytes(&unescape_path(path))))
+        .collect()
+}
+
+/// Undo the escaping GNU sha512sum applies to awkward filenames.
+///
+/// If a filename contains a newline (or backslash, or carriage return),
+/// the line is prefixed with `\` and those characters are escaped as
+/// `\n`, `\\`, and `\r`.
+fn unescape_path(path: &[u8]) -> Vec<u8> {
+    // if the line wasn't escaped, there is nothing to do
+    let escaped = match path.strip_prefix(b"\\") {
+        Some(escaped) => escaped,
+        None => return path.to_vec(),
+    };
+    let mut unescaped = Vec::with_capacity(escaped.len());
+    let mut bytes = escaped.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte != b'\\' {
+            unescaped.push(byte);
+            continue;
+        }
+        match bytes.next() {
+            Some(b'n') => unescaped.push(b'\n'),
+            Some(b'r') => unescaped.push(b'\r'),
+            Some(b'\\') => unescaped.push(b'\\'),
+            Some(&other) => unescaped.extend([b'\\', other]),
+            None => unescaped.push(b'\\'),
+        }
+    }
+    unescaped
+}
+
+/// Ensure that the configured sha512sum binary can be invoked.
+///
+/// The probe hashes `/dev/null`, which every sha512sum supports; unlike
+/// `--version`, which busybox-style builds reject. The configured args are
+/// left off, as flags like `--quiet` are only valid with `--check`.
+fn ensure_sha512sum(context: &Context) -> Result<()> {
+    let sha512sum_bin = &context.sha512sum_bin;
+    let output = Command::new(sha512sum_bin)
+        .arg("/dev/null")
+        .output()
+        .map_err(|e| format!("Unable to invoke SHA512SUM_BIN '{sha512sum_bin}': {e}"))?;
+    if !output.status.success() {
+        return Err(format!("SHA512SUM_BIN '{sha512sum_bin}' /dev/null failed").into());
+    }
+    trace!("Using {sha512sum_bin} {:?}", context.sha512sum_args);
+    Ok(())
+}
+
+/// Run `sha512sum {args} --check {file}` and capture the output.
+fn run_sha512sum(context: &Context, file: &Path) -> std::io::Result<Output> {
+    Command::new(&context.sha512sum_bin)
+        .args(&context.sha512sum_args)
+        .arg("--check")
+        .arg(file)
+        .output()
+}
+
+/// Run `sha512sum --check` over the work unit with several concurrent
+/// processes, and combine their output.
+///
+/// A SHA-512 digest can't be computed from independently hashed ranges of
+/// a file, so rather than splitting large files, the lines of the work unit
+/// are split into contiguous parts that are checked in parallel. This helps
+/// when the storage can serve several files at once, at the cost of more
+/// simultaneous I/O. Part files are written next to the work unit, and
+/// removed once the check is complete.
+///
+/// The combined stdout and exit status match a single process. The stderr
+/// is reported per part (e.g. one "computed checksum did NOT match" warning
+/// per failing part), with part paths rewritten to the work unit path.
+fn run_sha512sum_parallel(context: &Context, file: &Path) -> Result<Output> {
+    // split the lines of the work unit into groups; as bytes, because the
+    // paths in the work unit need not be valid UTF-8
+    let contents = fs::read(file)?;
+    let groups = group_checksum_lines(&contents);
+    if groups.is_empty() {
+        return Ok(run_sha512sum(context, file)?);
+    }
+    // split the groups into contiguous parts
+    let part_size = groups.len().div_ceil(context.sha512sum_parallelism);
+    let mut part_paths = Vec::new();
+    let write_result = groups
+        .chunks(part_size)
+        .enumerate()
+        .try_for_each(|(index, part)| {
+            let mut part_name = file.file_name().unwrap().to_os_string();
+            part_name.push(format!(".part{index}"));
+            let part_path = file.with_file_name(part_name);
+            // track the part before writing it, so it gets cleaned up
+            part_paths.push(part_path.clone());
+            let mut part_contents = Vec::new();
+            for line in part.iter().flatten() {
+                part_contents.extend_from_slice(line);
+                part_contents.push(b'\n');
+            }
+            fs::write(&part_path, part_contents)
+        });
+    // if we couldn't write all of the parts, clean up and bail out
+    if let Err(e) = write_result {
+        remove_parts(&part_paths);
+        return Err(e.into());
+    }
+    // check each of the parts in parallel
+    let outputs: Vec<std::io::Result<Output>> = thread::scope(|scope| {
+        let handles: Vec<_> = part_paths
+            .iter()
+            .map(|part_path| scope.spawn(|| run_sha512sum(context, part_path)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("sha512sum thread panicked"))
+            .collect()
+    });
+    // clean up the parts; we don't need them anymore
+    remove_parts(&part_paths);
+    // combine the output of each part, in order
+    let mut combined: Option<Output> = None;
+    let work_unit_path = file.as_os_str().as_bytes();
+    for (output, part_path) in outputs.into_iter().zip(&part_paths) {
+        let mut output = output?;
+        // messages about a part are really about the work unit
+        output.stderr = replace_bytes(
+            &output.stderr,
+            part_path.as_os_str().as_bytes(),
+            work_unit_path,
+        );
+        match combined.as_mut() {
+            None => combined = Some(output),
+            Some(combined) => {
+                // the first failure determines the exit status
+                if combined.status.success() {
+                    combined.status = output.status;
+                }
+                combined.stdout.extend(output.stdout);
+                combined.stderr.extend(output.stderr);
+            }
+        }
+    }
+    Ok(combined.expect("at least one part"))
+}
+
+/// Group the lines of a work unit for splitting into parts, so that every
+/// group holds exactly one checksum line.
+///
+/// `sha512sum --check` ignores blank lines, so they are dropped. Any other
+/// line without a checksum separator stays with the checksum line before
+/// it (or the first one), so that no part is made up entirely of lines
+/// that `sha512sum` would reject as improperly formatted.
+fn group_checksum_lines(contents: &[u8]) -> Vec<Vec<&[u8]>> {
+    let mut groups: Vec<Vec<&[u8]>> = Vec::new();
+    let mut leading = Vec::new();
+    for line in contents.split(|byte| *byte == b'\n') {
+        if line.is_empty() {
+            continue;
+        }
+        if checksum_separator(line).is_some() {
+            let mut group = std::mem::take(&mut leading);
+            group.push(line);
+            groups.push(group);
+        } else {
+            match groups.last_mut() {
+                Some(group) => group.push(line),
+                None => leading.push(line),
+            }
+        }
+    }
+    groups
+}
+
+/// Find the separator between the checksum and the path in a line of a
+/// checkfile; "{checksum}  {path}" or "{checksum} *{path}".
+fn checksum_separator(line: &[u8]) -> Option<usize> {
+    line.windows(2)
+        .position(|pair| pair == b"  " || pair == b" *")
+}
+
+/// Replace every occurrence of `from` in `haystack` with `to`.
+fn replace_bytes(haystack: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+    let mut replaced = Vec::with_capacity(haystack.len());
+    let mut rest = haystack;
+    while let Some(index) = rest.windows(from.len()).position(|window| window == from) {
+        replaced.extend_from_slice(&rest[..index]);
+        replaced.extend_from_slice(to);
+        rest = &rest[index + from.len()..];
+    }
+    replaced.extend_from_slice(rest);
+    replaced
+}
+
+/// Remove the part files written by `run_sha512sum_parallel`.
+fn remove_parts(part_paths: &[PathBuf]) {
+    for part_path in part_paths {
+        if let Err(e) = fs::remove_file(part_path) {
+            // a part that was never written is not an error
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Unable to remove {part_path:?}: {e}");
+            }
+        }
+    }
+}
+
+/// Determine the number of files, and their total size, that are listed
+/// in a work unit (a checkfile for `sha512sum --check`).
+///
+/// Files that cannot be stat'ed count towards the number of files, but do
+/// not contribute any bytes.
+fn measure_work_unit(file: &Path) -> WorkUnitSize {
+    let mut size = WorkUnitSize::default();
+    // read the contents of the work unit; as bytes, because the paths in
+    // the work unit need not be valid UTF-8
+    let contents = match fs::read(file) {
+        Ok(contents) => contents,
+        Err(e) => {
+            warn!("Unable to read {file:?} to measure it: {e}");
+            return size;
+        }
+    };
+    // each line looks like "{checksum}  {path}" or "{checksum} *{path}"
+    for line in contents.split(|byte| *byte == b'\n') {
+        let path = match checksum_separator(line) {
+            Some(index) => OsStr::from_bytes(&line[index + 2..]),
+            None => continue,
+        };
+        size.files += 1;
+        if let Ok(metadata) = fs::metadata(path) {
+            size.bytes += metadata.len();
+        }
+    }
+    size
+}
+
+/// Count the number of files in the provided directory that match the filter.
+fn count_files(dir: &Path, filter: &WorkUnitFilter) -> std::io::Result<u64> {
+    let mut count = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && filter.is_match(&path) {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Classify why a file failed verification, by examining its length
+/// and content.
+///
+/// Reading stops at the first non-zero byte, so only files that really
+/// are all zero bytes are read in their entirety.
+fn classify_failed_file(path: &Path) -> Classification {
+    // if we can't open the file, it's either missing or unreadable
+    let mut file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Classification::Missing,
+        Err(e) => {
+            warn!("Unable to open {path:?}: {e}");
+            return Classification::Unreadable;
+        }
+    };
+    // look for any content that isn't 0x00
+    let mut buffer = vec![0u8; 1024 * 1024];
+    let mut length = 0;
+    loop {
+        let count = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                warn!("Unable to read {path:?}: {e}");
+                return Classification::Unreadable;
+            }
+        };
+        length += count;
+        if buffer[..count].iter().any(|&byte| byte != 0) {
+            return Classification::ChecksumMismatch;
+        }
+    }
+    // we didn't find anything but zeros
+    if length == 0 {
+        return Classification::ZeroLength;
+    }
+    Classification::AllZeroBytes
+}
+
+/// Classify each of the files that failed verification.
+fn classify_failed_files(failed_files: &[PathBuf]) -> Vec<Failure> {
+    failed_files
+        .iter()
+        .map(|path| Failure {
+            path: path.to_string_lossy().to_string(),
+            classification: classify_failed_file(path),
+        })
+        .collect()
+}
+
+/// Write a note to the quarantine directory describing the failed work unit.
+fn write_note(
+    context: &Context,
+    file: &Path,
+    note_path: &Path,
+    output: &Output,
+    failures: &[Failure],
+) -> Result<()> {
+    let mut note_file = fs::File::create(note_path)?;
+    match context.note_format {
+        // write the command output to our note file
+        NoteFormat::Text => {
+            note_file.write_all(&output.stdout)?;
+            note_file.write_all("\n\n\n".as_bytes())?;
+            note_file.write_all(&output.stderr)?;
+            // followed by the classification of each failed file
+            if !failures.is_empty() {
+                note_file.write_all("\n\n\n".as_bytes())?;
+                for failure in failures {
+                    let line = format!("{}: {}\n", failure.path, failure.classification);
+                    note_file.write_all(line.as_bytes())?;
+                }
+            }
+        }
+        // write a structured note to our note file
+        NoteFormat::Json => {
+            let note = Note {
+                work_unit: file.to_string_lossy().to_string(),
+                exit_code: output.status.code().unwrap_or(-1),
+                failed_files: failures.iter().map(|f| f.path.clone()).collect(),
+                failures: failures.to_vec(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            };
+            serde_json::to_writer_pretty(&mut note_file, &note)?;
+        }
+    }
+    note_file.flush()?;
+    Ok(())
+}
+
 pub fn main() -> Result<()> {
     // enable logging
     env_logger::init();
     // load the application context
     let context = load_context();
     trace!("context: {context:#?}");
+    // make sure we'll be able to run sha512sum
+    // errors here terminate the program
+    ensure_sha512sum(&context)?;
     // get our work paths all set up
     let inbox_dir = Path::new(&context.inbox_dir);
     let outbox_dir = Path::new(&context.outbox_dir);
     let quarantine_dir = Path::new(&context.quarantine_dir);
     let work_dir = Path::new(&context.work_dir);
+    let filter = &context.work_unit_filter;
+    let is_match = |path: &Path| filter.is_match(path);
     // set up some things we'd like to keep track of
     let mut sent_to_quarantine = false;
-    let mut work_count = 0;
+    let report_interval = Duration::from_secs(context.throughput_report_seconds);
+    let mut cycle_start = Instant::now();
+    let mut last_report = Instant::now();
+    let mut throughput = Throughput::new(count_files(inbox_dir, filter).ok());
     // いつまでも
     loop {
         // try to claim the next file to work on it
         // errors here terminate the program
-        match next_file(inbox_dir, work_dir)? {
+        match next_file_matching(inbox_dir, work_dir, is_match)? {
             // if we managed to grab a file
             Some(file) => {
-                // increment the work counter
-                work_count += 1;
+                // determine how much work is in this work unit
+                let work_unit_size = measure_work_unit(&file);
                 // attempt to process the file
                 match process_file(&context, &file) {
                     // processing successful
@@ -165,13 +835,17 @@ pub fn main() -> Result<()> {
                         sent_to_quarantine = true;
                     }
                 }
+                // account for the work we did, and report on it periodically
+                throughput.record(&work_unit_size);
+                if last_report.elapsed() >= report_interval {
+                    info!("Throughput: {}", throughput.report(cycle_start.elapsed()));
+                    last_report = Instant::now();
+                }
             }
             // whoops, no work left to do
             None => {
-                // log about how much work we did
-                info!("Processed {work_count} file(s) this cycle.");
-                // reset the work counter
-                work_count = 0;
+                // log about how much work we did this cycle
+                info!("Summary: {}", throughput.report(cycle_start.elapsed()));
                 // if we were configured to run a single cycle
                 if context.run_once_and_die {
                     warn!("RUN_ONCE_AND_DIE = true; Work cycle is now complete.");
@@ -186,6 +860,10 @@ pub fn main() -> Result<()> {
                 // otherwise, just sleep until we check for more work
                 let work_sleep_seconds = context.work_sleep_seconds;
                 thread::sleep(Duration::from_secs(work_sleep_seconds));
+                // start accounting for the next work cycle
+                cycle_start = Instant::now();
+                last_report = Instant::now();
+                throughput = Throughput::new(count_files(inbox_dir, filter).ok());
             }
         }
     }
@@ -194,11 +872,12 @@ pub fn main() -> Result<()> {
 fn process_file(context: &Context, file: &Path) -> Result<()> {
     // log about processing the file
     info!("Processing: {file:?}");
-    // run `sha512sum --check {file}` and capture the output and exit code
-    let output = Command::new("sha512sum")
-        .arg("--check")
-        .arg(file)
-        .output()?;
+    // run `sha512sum {args} --check {file}` and capture the output and exit code
+    let output = if context.sha512sum_parallelism > 1 {
+        run_sha512sum_parallel(context, file)?
+    } else {
+        run_sha512sum(context, file)?
+    };
     // if the environment requested a delay for debugging purposes
     if context.debug_delay_seconds > 0 {
         // sleep the required number of seconds to allow for debugging
@@ -209,12 +888,13 @@ fn process_file(context: &Context, file: &Path) -> Result<()> {
         // determine the path to our note file: "{context.quarantine_dir}/{file}.note"
         let quarantine_dir = Path::new(&context.quarantine_dir);
         let note_path = build_note_path(quarantine_dir, file);
+        // figure out why each of the failed files failed
+        let failures = classify_failed_files(&parse_failed_files(&output.stdout));
+        for failure in &failures {
+            warn!("Failed: {} ({})", failure.path, failure.classification);
+        }
         // write the command output to our note file
-        let mut note_file = fs::File::create(&note_path)?;
-        note_file.write_all(&output.stdout)?;
-        note_file.write_all("\n\n\n".as_bytes())?;
-        note_file.write_all(&output.stderr)?;
-        note_file.flush()?;
+        write_note(context, file, &note_path, &output, &failures)?;
         // indicate to the caller that the command didn't succeed
         let exit_code = output.status.code().unwrap_or(-1);
         return Err(format!("Exit code {exit_code}: See {note_path:?}").into());
@@ -229,10 +909,479 @@ fn process_file(context: &Context, file: &Path) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+
+    #[test]
+    fn test_note_format_from_str() {
+        assert_eq!("text".parse::<NoteFormat>(), Ok(NoteFormat::Text));
+        assert_eq!("json".parse::<NoteFormat>(), Ok(NoteFormat::Json));
+        assert!("yaml".parse::<NoteFormat>().is_err());
+    }
+
+    #[test]
+    fn test_parse_failed_files() {
+        let stdout = "\
+/data/exp/IceCube/2024/filtered/PFFilt/0401/PFFilt_1.tar.bz2: OK
+/data/exp/IceCube/2024/filtered/PFFilt/0401/PFFilt_2.tar.bz2: FAILED
+/data/exp/IceCube/2024/filtered/PFFilt/0401/PFFilt_3.tar.bz2: OK
+/data/exp/IceCube/2024/filtered/PFFilt/0401/PFFilt_4.tar.bz2: FAILED open or read
+";
+        let failed_files = parse_failed_files(stdout.as_bytes());
+        assert_eq!(
+            failed_files,
+            vec![
+                PathBuf::from("/data/exp/IceCube/2024/filtered/PFFilt/0401/PFFilt_2.tar.bz2"),
+                PathBuf::from("/data/exp/IceCube/2024/filtered/PFFilt/0401/PFFilt_4.tar.bz2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_failed_files_all_ok() {
+        let stdout = "/data/exp/IceCube/2024/a.tar.bz2: OK\n/data/exp/IceCube/2024/b.tar.bz2: OK\n";
+        assert!(parse_failed_files(stdout.as_bytes()).is_empty());
+        assert!(parse_failed_files(b"").is_empty());
+    }
+
+    #[test]
+    fn test_parse_failed_files_escaped_and_non_utf8() {
+        let stdout = b"\\/data/a\\nb\\\\c.data: FAILED\n/data/bad\xff.data: FAILED\n";
+        assert_eq!(
+            parse_failed_files(stdout),
+            vec![
+                PathBuf::from("/data/a\nb\\c.data"),
+                PathBuf::from(OsStr::from_bytes(b"/data/bad\xff.data")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify_awkward_filenames() {
+        let dir = tempfile::tempdir().unwrap();
+        let non_utf8 = dir.path().join(OsStr::from_bytes(b"bad\xff.data"));
+        let newline = dir.path().join("new\nline.data");
+        let mut checkfile = Vec::new();
+        for path in [&non_utf8, &newline] {
+            fs::write(path, "good contents").unwrap();
+            let output = Command::new("sha512sum").arg(path).output().unwrap();
+            checkfile.extend(output.stdout);
+            // corrupt the file after its checksum was computed
+            fs::write(path, [0u8; 13]).unwrap();
+        }
+        let work_unit = dir.path().join("check_work.aa");
+        fs::write(&work_unit, checkfile).unwrap();
+        let context = test_context(dir.path(), Path::new("sha512sum"), &[]);
+        let output = run_sha512sum(&context, &work_unit).unwrap();
+        let failures = classify_failed_files(&parse_failed_files(&output.stdout));
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].path, non_utf8.to_string_lossy());
+        assert_eq!(failures[0].classification, Classification::AllZeroBytes);
+        assert_eq!(failures[1].path, newline.to_string_lossy());
+        assert_eq!(failures[1].classification, Classification::AllZeroBytes);
+    }
+
+    #[test]
+    fn test_throughput_accounting() {
+        let mut throughput = Throughput::new(Some(4));
+        throughput.record(&WorkUnitSize {
+            files: 10,
+            bytes: 1_000_000,
+        });
+        throughput.record(&WorkUnitSize {
+            files: 30,
+            bytes: 9_000_000,
+        });
+        assert_eq!(throughput.work_units, 2);
+        assert_eq!(throughput.files, 40);
+        assert_eq!(throughput.bytes, 10_000_000);
+        let elapsed = Duration::from_secs(20);
+        assert_eq!(throughput.files_per_sec(elapsed), 2.0);
+        assert_eq!(throughput.mb_per_sec(elapsed), 0.5);
+        // 2 work units in 20 seconds; 2 more to go
+        assert_eq!(throughput.eta(elapsed), Some(Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn test_throughput_unknown_total_or_no_work() {
+        let mut throughput = Throughput::new(Some(4));
+        assert_eq!(throughput.eta(Duration::from_secs(20)), None);
+        assert_eq!(throughput.files_per_sec(Duration::ZERO), 0.0);
+        let mut throughput_unknown = Throughput::new(None);
+        throughput_unknown.record(&WorkUnitSize { files: 1, bytes: 1 });
+        assert_eq!(throughput_unknown.eta(Duration::from_secs(20)), None);
+        // more work units than expected (new arrivals) doesn't underflow
+        for _ in 0..5 {
+            throughput.record(&WorkUnitSize::default());
+        }
+        assert_eq!(
+            throughput.eta(Duration::from_secs(20)),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_measure_work_unit() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_a = dir.path().join("a.data");
+        let data_b = dir.path().join("b.data");
+        fs::write(&data_a, [0u8; 100]).unwrap();
+        fs::write(&data_b, [0u8; 23]).unwrap();
+        let work_unit = dir.path().join("check_work.aa");
+        let missing = dir.path().join("missing.data");
+        let contents = format!(
+            "abc123  {}\ndef456 *{}\n0123ab  {}\n",
+            data_a.display(),
+            data_b.display(),
+            missing.display()
+        );
+        fs::write(&work_unit, contents).unwrap();
+        let size = measure_work_unit(&work_unit);
+        assert_eq!(
+            size,
+            WorkUnitSize {
+                files: 3,
+                bytes: 123
+            }
+        );
+    }
+
+    fn test_context(dir: &Path, sha512sum_bin: &Path, sha512sum_args: &[&str]) -> Context {
+        Context {
+            debug_delay_seconds: 0,
+            inbox_dir: dir.join("inbox").to_string_lossy().to_string(),
+            note_format: NoteFormat::Text,
+            outbox_dir: dir.join("outbox").to_string_lossy().to_string(),
+            quarantine_dir: dir.to_string_lossy().to_string(),
+            run_once_and_die: true,
+            sha512sum_args: sha512sum_args.iter().map(|arg| arg.to_string()).collect(),
+            sha512sum_bin: sha512sum_bin.to_string_lossy().to_string(),
+            sha512sum_parallelism: 1,
+            throughput_report_seconds: 60,
+            work_dir: dir.join("work").to_string_lossy().to_string(),
+            work_sleep_seconds: 60,
+            work_unit_filter: WorkUnitFilter {
+                since: None,
+                source: DateFilterSource::Filename,
+                until: None,
+            },
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_filename_date() {
+        let check = |name: &str| filename_date(Path::new(name));
+        assert_eq!(check("check_work.2024-04-01.aa"), Some(date(2024, 4, 1)));
+        assert_eq!(
+            check("/inbox/20240630_check_work.ab"),
+            Some(date(2024, 6, 30))
+        );
+        assert_eq!(
+            check("check_work_2024-13-01_20240501.ac"),
+            Some(date(2024, 5, 1))
+        );
+        assert_eq!(check("check_work.aa"), None);
+        assert_eq!(check("check_work.20241301.aa"), None);
+    }
+
+    #[test]
+    fn test_work_unit_filter_filename() {
+        let filter = WorkUnitFilter {
+            since: Some(date(2024, 4, 1)),
+            source: DateFilterSource::Filename,
+            until: Some(date(2024, 7, 1)),
+        };
+        assert!(filter.is_match(Path::new("check_work.2024-04-01.aa")));
+        assert!(filter.is_match(Path::new("check_work.2024-06-30.aa")));
+        assert!(!filter.is_match(Path::new("check_work.2024-03-31.aa")));
+        assert!(!filter.is_match(Path::new("check_work.2024-07-01.aa")));
+        assert!(!filter.is_match(Path::new("check_work.aa")));
+        let open_ended = WorkUnitFilter {
+            since: Some(date(2024, 4, 1)),
+            source: DateFilterSource::Filename,
+            until: None,
+        };
+        assert!(open_ended.is_match(Path::new("check_work.20990101.aa")));
+        let unfiltered = WorkUnitFilter {
+            since: None,
+            source: DateFilterSource::Filename,
+            until: None,
+        };
+        assert!(unfiltered.is_match(Path::new("check_work.aa")));
+    }
+
+    #[test]
+    fn test_work_unit_filter_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let set_mtime = |name: &str, date: NaiveDate| {
+            let path = dir.path().join(name);
+            let file = fs::File::create(&path).unwrap();
+            let mtime = date.and_hms_opt(12, 0, 0).unwrap().and_utc();
+            file.set_modified(mtime.into()).unwrap();
+            path
+        };
+        let before = set_mtime("check_work.aa", date(2024, 3, 31));
+        let during = set_mtime("check_work.ab", date(2024, 5, 15));
+        let after = set_mtime("check_work.ac", date(2024, 7, 1));
+        let filter = WorkUnitFilter {
+            since: Some(date(2024, 4, 1)),
+            source: DateFilterSource::Mtime,
+            until: Some(date(2024, 7, 1)),
+        };
+        assert!(!filter.is_match(&before));
+        assert!(filter.is_match(&during));
+        assert!(!filter.is_match(&after));
+        assert!(!filter.is_match(&dir.path().join("missing")));
+        assert_eq!(count_files(dir.path(), &filter).unwrap(), 1);
+    }
+
+    fn write_stub(path: &Path, script: &str) {
+        use std::os::unix::fs::PermissionsExt;
+        fs::write(path, script).unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_process_file_uses_configured_sha512sum() {
+        let dir = tempfile::tempdir().unwrap();
+        let args_path = dir.path().join("args.txt");
+        let stub = dir.path().join("stub-sha512sum");
+        write_stub(
+            &stub,
+            &format!("#!/bin/sh\necho \"$@\" > {}\n", args_path.display()),
+        );
+        let work_unit = dir.path().join("check_work.aa");
+        fs::write(&work_unit, "").unwrap();
+        let context = test_context(dir.path(), &stub, &["--quiet", "--strict"]);
+        ensure_sha512sum(&context).unwrap();
+        process_file(&context, &work_unit).unwrap();
+        let args = fs::read_to_string(&args_path).unwrap();
+        assert_eq!(
+            args.trim_end(),
+            format!("--quiet --strict --check {}", work_unit.display())
+        );
+    }
+
+    #[test]
+    fn test_ensure_sha512sum_accepts_minimal_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        // like busybox, reject any long option we don't know about
+        let minimal = dir.path().join("minimal-sha512sum");
+        write_stub(
+            &minimal,
+            "#!/bin/sh\ncase \"$1\" in --*) exit 1;; esac\nexit 0\n",
+        );
+        let context = test_context(dir.path(), &minimal, &["--quiet"]);
+        ensure_sha512sum(&context).unwrap();
+    }
+
+    #[test]
+    fn test_ensure_sha512sum_rejects_bad_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("no-such-sha512sum");
+        let context = test_context(dir.path(), &missing, &[]);
+        assert!(ensure_sha512sum(&context).is_err());
+        let failing = dir.path().join("failing-sha512sum");
+        write_stub(&failing, "#!/bin/sh\nexit 1\n");
+        let context = test_context(dir.path(), &failing, &[]);
+        assert!(ensure_sha512sum(&context).is_err());
+    }
+
+    #[test]
+    fn test_classify_failed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let zero_length = dir.path().join("zero_length.data");
+        let all_zero = dir.path().join("all_zero.data");
+        let mismatch = dir.path().join("mismatch.data");
+        fs::write(&zero_length, []).unwrap();
+        fs::write(&all_zero, vec![0u8; 3 * 1024 * 1024 + 17]).unwrap();
+        let mut content = vec![0u8; 2 * 1024 * 1024];
+        content[1024 * 1024 + 5] = 0x42;
+        fs::write(&mismatch, content).unwrap();
+        let missing = dir.path().join("missing.data");
+        assert_eq!(
+            classify_failed_file(&zero_length),
+            Classification::ZeroLength
+        );
+        assert_eq!(
+            classify_failed_file(&all_zero),
+            Classification::AllZeroBytes
+        );
+        assert_eq!(
+            classify_failed_file(&mismatch),
+            Classification::ChecksumMismatch
+        );
+        assert_eq!(classify_failed_file(&missing), Classification::Missing);
+        // a directory opens fine on Linux, but fails to read (EISDIR)
+        assert_eq!(classify_failed_file(dir.path()), Classification::Unreadable);
+    }
+
+    #[test]
+    fn test_classification_serialization() {
+        let failure = Failure {
+            path: "/data/exp/IceCube/2024/a.tar.bz2".to_string(),
+            classification: Classification::AllZeroBytes,
+        };
+        assert_eq!(
+            serde_json::to_string(&failure).unwrap(),
+            r#"{"path":"/data/exp/IceCube/2024/a.tar.bz2","classification":"all-zero-bytes"}"#
+        );
+        assert_eq!(Classification::ZeroLength.to_string(), "zero-length");
+        assert_eq!(Classification::Unreadable.to_string(), "unreadable");
+        assert_eq!(
+            Classification::ChecksumMismatch.to_string(),
+            "checksum-mismatch"
+        );
+    }
+
+    #[test]
+    fn test_parallel_and_serial_sha512sum_agree() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut checkfile = String::new();
+        for index in 0..7 {
+            let data = dir.path().join(format!("file{index}.data"));
+            fs::write(&data, format!("contents of file {index}")).unwrap();
+            let output = Command::new("sha512sum").arg(&data).output().unwrap();
+            checkfile.push_str(&String::from_utf8_lossy(&output.stdout));
+        }
+        // include a large file, the case this option is meant for
+        let large: Vec<u8> = (0..8 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let large_path = dir.path().join("large.data");
+        fs::write(&large_path, large).unwrap();
+        let output = Command::new("sha512sum").arg(&large_path).output().unwrap();
+        checkfile.push_str(&String::from_utf8_lossy(&output.stdout));
+        // corrupt one of the files after its checksum was computed
+        fs::write(dir.path().join("file4.data"), [0u8; 20]).unwrap();
+        let work_unit = dir.path().join("check_work.aa");
+        fs::write(&work_unit, checkfile).unwrap();
+        let mut context = test_context(dir.path(), Path::new("sha512sum"), &[]);
+        let serial = run_sha512sum(&context, &work_unit).unwrap();
+        for parallelism in [2, 3, 7, 16] {
+            context.sha512sum_parallelism = parallelism;
+            let parallel = run_sha512sum_parallel(&context, &work_unit).unwrap();
+            assert_eq!(parallel.stdout, serial.stdout);
+            assert_eq!(parallel.status.success(), serial.status.success());
+        }
+        assert!(!serial.status.success());
+        assert_eq!(
+            parse_failed_files(&serial.stdout),
+            vec![dir.path().join("file4.data")]
+        );
+        // the parts were cleaned up after the check
+        let remaining = fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(remaining, 9);
+    }
+
+    #[test]
+    fn test_parallel_sha512sum_blank_and_malformed_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut checksum_lines = Vec::new();
+        for index in 0..3 {
+            let data = dir.path().join(format!("file{index}.data"));
+            fs::write(&data, format!("contents of file {index}")).unwrap();
+            let output = Command::new("sha512sum").arg(&data).output().unwrap();
+            checksum_lines.push(String::from_utf8_lossy(&output.stdout).to_string());
+        }
+        // blank lines inside and at the end, and a malformed line up front
+        let checkfile = format!(
+            "garbage\n{}\n\n{}\n{}\n\n\n",
+            checksum_lines[0], checksum_lines[1], checksum_lines[2]
+        );
+        let work_unit = dir.path().join("check_work.aa");
+        fs::write(&work_unit, checkfile).unwrap();
+        let mut context = test_context(dir.path(), Path::new("sha512sum"), &[]);
+        let serial = run_sha512sum(&context, &work_unit).unwrap();
+        assert!(serial.status.success());
+        for parallelism in [2, 3, 4, 8] {
+            context.sha512sum_parallelism = parallelism;
+            let parallel = run_sha512sum_parallel(&context, &work_unit).unwrap();
+            assert!(parallel.status.success());
+            assert_eq!(parallel.stdout, serial.stdout);
+        }
+    }
+
+    #[test]
+    fn test_parallel_sha512sum_stderr_names_work_unit() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path().join("file.data");
+        fs::write(&data, "contents").unwrap();
+        let output = Command::new("sha512sum").arg(&data).output().unwrap();
+        let mut checkfile = output.stdout.clone();
+        checkfile.extend(b"not a checksum line\n");
+        checkfile.extend(output.stdout);
+        let work_unit = dir.path().join("check_work.aa");
+        fs::write(&work_unit, checkfile).unwrap();
+        let mut context = test_context(dir.path(), Path::new("sha512sum"), &["--strict", "--warn"]);
+        context.sha512sum_parallelism = 2;
+        let parallel = run_sha512sum_parallel(&context, &work_unit).unwrap();
+        let stderr = String::from_utf8_lossy(&parallel.stderr);
+        assert!(!parallel.status.success());
+        assert!(stderr.contains(&*work_unit.to_string_lossy()), "{stderr}");
+        assert!(!stderr.contains(".part"), "{stderr}");
+    }
+
+    #[test]
+    fn test_group_checksum_lines() {
+        let groups = group_checksum_lines(b"junk\nabc  /a\n\nmore junk\ndef *b\n\n");
+        assert_eq!(
+            groups,
+            vec![
+                vec![b"junk".as_slice(), b"abc  /a", b"more junk"],
+                vec![b"def *b".as_slice()],
+            ]
+        );
+        assert!(group_checksum_lines(b"\n\njunk\n").is_empty());
+        assert_eq!(
+            replace_bytes(b"x.part0: x.part0!", b"x.part0", b"x"),
+            b"x: x!".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_parallel_sha512sum_non_utf8_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut checkfile = Vec::new();
+        for name in [b"good.data".as_slice(), b"bad\xff.data".as_slice()] {
+            let data = dir.path().join(OsStr::from_bytes(name));
+            fs::write(&data, "good contents").unwrap();
+            let output = Command::new("sha512sum").arg(&data).output().unwrap();
+            checkfile.extend(output.stdout);
+        }
+        let work_unit = dir.path().join("check_work.aa");
+        fs::write(&work_unit, checkfile).unwrap();
+        let mut context = test_context(dir.path(), Path::new("sha512sum"), &[]);
+        let serial = run_sha512sum(&context, &work_unit).unwrap();
+        context.sha512sum_parallelism = 2;
+        let parallel = run_sha512sum_parallel(&context, &work_unit).unwrap();
+        assert!(serial.status.success());
+        assert!(parallel.status.success());
+        assert_eq!(parallel.stdout, serial.stdout);
+    }
+
+    #[test]
+    fn test_parallel_sha512sum_cleans_up_on_write_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let work_unit = dir.path().join("check_work.aa");
+        fs::write(
+            &work_unit,
+            "abc123  /a.data\ndef456  /b.data\n0123ab  /c.data\n",
+        )
+        .unwrap();
+        // a directory in the way of the second part makes its write fail
+        fs::create_dir(dir.path().join("check_work.aa.part1")).unwrap();
+        let mut context = test_context(dir.path(), Path::new("sha512sum"), &[]);
+        context.sha512sum_parallelism = 3;
+        assert!(run_sha512sum_parallel(&context, &work_unit).is_err());
+        assert!(!dir.path().join("check_work.aa.part0").exists());
+        assert!(!dir.path().join("check_work.aa.part2").exists());
+    }
 
     #[test]
-    fn test_always_succeed() {
-        assert!(true);
+    fn test_build_note_path() {
+        let note_path = build_note_path(Path::new("/quarantine"), Path::new("/work/check_work.aa"));
+        assert_eq!(note_path, PathBuf::from("/quarantine/check_work.aa.note"));
     }
 }